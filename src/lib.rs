@@ -1,8 +1,7 @@
 // Standard library imports
-use std::collections::hash_map::IntoIter;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::sync::{
     atomic::{AtomicU64, Ordering},
     Mutex,
@@ -18,32 +17,432 @@ use pyo3::exceptions::{PyIOError, PyKeyError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::PyResult;
 use rayon::prelude::*;
+use roaring::RoaringTreemap;
 use serde::{Deserialize, Serialize};
-use sourmash::encodings::revcomp;
 use sourmash::encodings::HashFunctions;
 use sourmash::signature::SeqToHashes;
 
 // Set version variable
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Format tag written at the start of files produced by `save_binary`,
+/// letting `load` tell them apart from the legacy JSON+gzip format `save`
+/// produces (gzip members start with `\x1f\x8b`, so this can't collide).
+const BINARY_MAGIC: &[u8; 4] = b"OXLB";
+
+/// The alphabet a `KmerCountTable` hashes k-mers in. `Dna` is
+/// double-stranded, so its k-mers are canonicalized against their reverse
+/// complement; the amino-acid alphabets are not, so they're hashed (and
+/// reported) exactly as the input sequence reads.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Dna,
+    Protein,
+    Dayhoff,
+    Hp,
+}
+
+impl Encoding {
+    /// Parse the `encoding` constructor argument, as a `KmerCountTable`
+    /// would any other user-facing string choice.
+    fn parse(s: &str) -> PyResult<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "dna" => Ok(Encoding::Dna),
+            "protein" => Ok(Encoding::Protein),
+            "dayhoff" => Ok(Encoding::Dayhoff),
+            "hp" => Ok(Encoding::Hp),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown encoding '{}': expected one of dna, protein, dayhoff, hp",
+                s
+            ))),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Dna => "dna",
+            Encoding::Protein => "protein",
+            Encoding::Dayhoff => "dayhoff",
+            Encoding::Hp => "hp",
+        }
+    }
+
+    /// The sourmash hash function matching this alphabet.
+    fn hash_function(&self) -> HashFunctions {
+        match self {
+            Encoding::Dna => HashFunctions::Murmur64Dna,
+            Encoding::Protein => HashFunctions::Murmur64Protein,
+            Encoding::Dayhoff => HashFunctions::Murmur64Dayhoff,
+            Encoding::Hp => HashFunctions::Murmur64Hp,
+        }
+    }
+
+    /// Whether this alphabet is double-stranded DNA, and so should be
+    /// canonicalized by reverse complement. The amino-acid alphabets have
+    /// no complementary strand, so canonicalization is skipped for them.
+    fn is_nucleotide(&self) -> bool {
+        matches!(self, Encoding::Dna)
+    }
+}
+
+/// Counting backend used by a `KmerCountTable`.
+///
+/// `Exact` stores one `u64` counter per distinct hash, same as the
+/// original implementation. `Cms` instead uses a fixed-size Count-Min
+/// Sketch: `depth` independent counter rows, each at least `width` slots
+/// long (see `CmsTable`), which trades exact counts for a constant memory
+/// footprint.
+#[derive(Serialize, Deserialize, Debug)]
+enum CountBackend {
+    Exact(HashMap<u64, u64>),
+    Cms(CmsTable),
+}
+
+/// Smallest prime `>= n` (falling back to a trial-division search), used
+/// to size each `CmsTable` row. Distinct, mutually-prime-ish row sizes
+/// mean a collision in one row is unlikely to recur in another, unlike
+/// sharing a single `width` across all rows.
+fn next_prime(n: usize) -> usize {
+    fn is_prime(n: usize) -> bool {
+        if n < 2 {
+            return false;
+        }
+        if n % 2 == 0 {
+            return n == 2;
+        }
+        let mut i = 3;
+        while i * i <= n {
+            if n % i == 0 {
+                return false;
+            }
+            i += 2;
+        }
+        true
+    }
+
+    let mut candidate = n.max(2);
+    while !is_prime(candidate) {
+        candidate += 1;
+    }
+    candidate
+}
+
+/// A Count-Min Sketch: `depth` counter rows, each sized to a distinct
+/// prime at or above `width` (rather than sharing one `width`), so that a
+/// collision pattern in one row's modulus is unlikely to line up with
+/// another row's, minimizing correlated overcounts across rows.
+///
+/// For a hash value, `depth` table positions are derived by splitting the
+/// hash into two 32-bit halves `h1`/`h2` and computing, for row `i`,
+/// `(h1.wrapping_add((i as u32).wrapping_mul(h2))) % widths[i]`. Incrementing touches
+/// every row; querying returns the minimum across rows, which can only
+/// over-estimate the true count (the standard Count-Min guarantee).
+///
+/// Deliberately, this does *not* keep a set of distinct hashes seen: doing
+/// so would make memory usage grow with the number of distinct k-mers,
+/// defeating the entire point of a bounded-memory counting backend (and
+/// making it strictly worse than the `Exact` backend it replaces, given
+/// the extra counter rows on top). Like khmer's own Count-Min-based
+/// tables, this means operations that require enumerating which hashes
+/// were observed (`histo`, `hashes`, `dump`, set operations, ...) are not
+/// available for this backend; see `KmerCountTable::require_exact_backend`.
+#[derive(Serialize, Deserialize, Debug)]
+struct CmsTable {
+    widths: Vec<usize>,
+    depth: usize,
+    tables: Vec<Vec<u32>>,
+}
+
+impl CmsTable {
+    fn new(width: usize, depth: usize) -> Self {
+        // Space candidates `width/depth` apart (rather than `i` apart) so
+        // that at realistic widths the prime gap between consecutive
+        // candidates is reliably smaller than the spacing: two rows
+        // landing on the same prime would make their `positions()` collide
+        // in lockstep for any hash pair sharing `h1` and congruent in
+        // `h2`, defeating the whole point of independent rows.
+        let stride = (width / depth.max(1)).max(1);
+        let widths: Vec<usize> = (0..depth).map(|i| next_prime(width + i * stride)).collect();
+        let tables = widths.iter().map(|&w| vec![0u32; w]).collect();
+        Self {
+            widths,
+            depth,
+            tables,
+        }
+    }
+
+    /// Estimate of the one-sided relative overcount error: with `depth`
+    /// independent rows of average width `w`, a query overestimates the
+    /// true count by more than `estimate_error() * total_items_inserted`
+    /// with probability at most `e^-depth` (the standard Count-Min
+    /// Sketch guarantee, using Euler's number since each row is an
+    /// independent hash).
+    fn estimate_error(&self) -> f64 {
+        let avg_width = self.widths.iter().sum::<usize>() as f64 / self.depth as f64;
+        std::f64::consts::E / avg_width
+    }
+
+    /// Derive the `depth` table positions for a hash value.
+    fn positions(&self, hash: u64) -> Vec<usize> {
+        let h1 = (hash & 0xFFFF_FFFF) as u32;
+        let h2 = (hash >> 32) as u32;
+        (0..self.depth)
+            .map(|i| (h1.wrapping_add((i as u32).wrapping_mul(h2))) as usize % self.widths[i])
+            .collect()
+    }
+
+    /// Increment every row for this hash by 1, returning the new estimate.
+    fn increment(&mut self, hash: u64) -> u64 {
+        let mut estimate = u32::MAX;
+        for (row, pos) in self.positions(hash).into_iter().enumerate() {
+            self.tables[row][pos] = self.tables[row][pos].saturating_add(1);
+            estimate = estimate.min(self.tables[row][pos]);
+        }
+        estimate as u64
+    }
+
+    /// Force every row for this hash to `count` (used by `__setitem__`).
+    fn set(&mut self, hash: u64, count: u64) {
+        let value = count.min(u32::MAX as u64) as u32;
+        for (row, pos) in self.positions(hash).into_iter().enumerate() {
+            self.tables[row][pos] = value;
+        }
+    }
+
+    /// The Count-Min estimate for a hash: the minimum across all rows.
+    /// Since no exact seen-set is kept, this can return a (one-sided,
+    /// over-estimating) false-positive nonzero count for a hash that was
+    /// never inserted, if it collides in every row with ones that were.
+    fn get(&self, hash: u64) -> u64 {
+        self.positions(hash)
+            .into_iter()
+            .enumerate()
+            .map(|(row, pos)| self.tables[row][pos])
+            .min()
+            .unwrap_or(0) as u64
+    }
+}
+
+/// Precision for the HyperLogLog register array: `2^HLL_P` registers,
+/// giving a standard error of about `1.04/sqrt(2^HLL_P)` (~0.8%).
+const HLL_P: u32 = 14;
+
+/// HyperLogLog sketch tracking the number of distinct hashes ever passed
+/// to `count_hash`/`consume`, independent of the counting backend. Unlike
+/// `counts`, registers are never removed, so `cardinality` stays accurate
+/// even after `mincut`/`maxcut`/`drop` have thinned the exact table.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct HllSketch {
+    p: u32,
+    registers: Vec<u8>,
+}
+
+impl HllSketch {
+    fn new(p: u32) -> Self {
+        Self {
+            p,
+            registers: vec![0u8; 1usize << p],
+        }
+    }
+
+    /// Update the register for `hash`: index by the top `p` bits, value
+    /// by the position of the leftmost 1 bit (`rho`) among the rest.
+    fn add_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - self.p)) as usize;
+        let remainder = hash << self.p;
+        let max_rho = (64 - self.p + 1) as u8;
+        let rho = (remainder.leading_zeros() + 1).min(max_rho as u32) as u8;
+        if rho > self.registers[index] {
+            self.registers[index] = rho;
+        }
+    }
+
+    /// Union this sketch with another of the same precision.
+    fn merge(&mut self, other: &HllSketch) {
+        for (a, b) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if *b > *a {
+                *a = *b;
+            }
+        }
+    }
+
+    /// Estimate the number of distinct hashes seen, applying the
+    /// small-range linear-counting correction when appropriate.
+    fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&r| 2f64.powi(-(r as i32)))
+            .sum();
+        let raw_estimate = alpha * m * m / sum;
+
+        if raw_estimate <= 2.5 * m {
+            let zero_registers = self.registers.iter().filter(|&&r| r == 0).count();
+            if zero_registers > 0 {
+                return m * (m / zero_registers as f64).ln();
+            }
+        }
+
+        raw_estimate
+    }
+}
+
+/// Number of `u64` words in a `PackedKmer`. 8 words covers k-mers up to
+/// 256 bases, comfortably above `ksize: u8`'s maximum of 255.
+const PACKED_WORDS: usize = 8;
+
+/// A canonical k-mer packed 2 bits/base (A=00, C=01, G=10, T=11) into a
+/// fixed-size stack array, following the fixed-width encoding used by
+/// rust-debruijn's kmer module. This replaces storing a full `String`
+/// per k-mer in `hash_to_kmer`. Every entry holds the whole
+/// `PACKED_WORDS`-word array whatever the k-mer length, so an entry
+/// takes the same space for k<=32 as for k=255.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct PackedKmer {
+    // Bases are packed MSB-first within each word (first base in the
+    // highest-order bits of `words[0]`), so the derived `Ord` on `words`
+    // agrees with lexicographic order on the decoded string.
+    words: [u64; PACKED_WORDS],
+    len: u8,
+}
+
+impl PackedKmer {
+    fn base_to_bits(base: u8) -> u64 {
+        match base {
+            b'A' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            _ => 0, // canon() already rejects non-ACGT input
+        }
+    }
+
+    fn bits_to_base(bits: u64) -> u8 {
+        match bits {
+            0 => b'A',
+            1 => b'C',
+            2 => b'G',
+            _ => b'T',
+        }
+    }
+
+    /// Pack an uppercase ACGT string into its 2-bit representation.
+    fn encode(kmer: &str) -> Self {
+        let mut words = [0u64; PACKED_WORDS];
+        for (i, base) in kmer.bytes().enumerate() {
+            let word = i / 32;
+            let shift = 62 - (i % 32) * 2;
+            words[word] |= Self::base_to_bits(base) << shift;
+        }
+        Self {
+            words,
+            len: kmer.len() as u8,
+        }
+    }
+
+    /// Decode back to an uppercase DNA string.
+    fn decode(&self) -> String {
+        let mut s = String::with_capacity(self.len as usize);
+        for i in 0..self.len as usize {
+            let word = i / 32;
+            let shift = 62 - (i % 32) * 2;
+            let bits = (self.words[word] >> shift) & 0b11;
+            s.push(Self::bits_to_base(bits) as char);
+        }
+        s
+    }
+
+    /// Reverse complement, computed directly on the packed form: reverse
+    /// the order of 2-bit groups and complement each one (`bits ^ 0b11`,
+    /// since A/T are `00`/`11` and C/G are `01`/`10`), avoiding a decode
+    /// round-trip through `String`.
+    fn revcomp(&self) -> Self {
+        let len = self.len as usize;
+        let mut words = [0u64; PACKED_WORDS];
+        for i in 0..len {
+            let src = len - 1 - i;
+            let src_word = src / 32;
+            let src_shift = 62 - (src % 32) * 2;
+            let comp_bits = ((self.words[src_word] >> src_shift) & 0b11) ^ 0b11;
+
+            let dst_word = i / 32;
+            let dst_shift = 62 - (i % 32) * 2;
+            words[dst_word] |= comp_bits << dst_shift;
+        }
+        Self { words, len: self.len }
+    }
+
+    /// The canonical form: the lexicographically smaller of this k-mer or
+    /// its reverse complement, compared as packed word arrays rather than
+    /// byte-by-byte strings (this is what the derived `Ord` on `words` is
+    /// for — see the packing-order comment above).
+    fn canonical(&self) -> Self {
+        let rc = self.revcomp();
+        if *self <= rc {
+            *self
+        } else {
+            rc
+        }
+    }
+}
+
 #[pyclass]
 #[derive(Serialize, Deserialize, Debug)]
 /// Basic KmerCountTable struct, mapping hashes to counts.
 struct KmerCountTable {
-    counts: HashMap<u64, u64>,
+    backend: CountBackend,
     pub ksize: u8,
     version: String,
     consumed: u64,
     store_kmers: bool, // Store hash:kmer mapping if true
-    hash_to_kmer: Option<HashMap<u64, String>>,
+    hash_to_kmer: Option<HashMap<u64, PackedKmer>>,
+    hll: HllSketch, // Tracks distinct-hash cardinality independent of backend
+    scaled: Option<u64>, // FracMinHash downsampling factor, if enabled
+    encoding: Encoding, // Alphabet k-mers are hashed in (dna/protein/dayhoff/hp)
+    // Compressed presence set of every observed hash, kept in sync with the
+    // `Exact` backend so `union`/`intersection`/`jaccard`/`containment` can
+    // use roaring bitmap AND/OR instead of hashing-based set ops. Left
+    // empty for the `Cms` backend, which keeps no such set at all (see
+    // `CmsTable`'s doc comment) -- those operations error out for it via
+    // `require_exact_backend` instead of silently comparing empty bitmaps.
+    // Not serialized directly (to avoid coupling the on-disk format to
+    // `roaring`'s internal encoding); rebuilt from the backend on load.
+    #[serde(skip)]
+    presence: RoaringTreemap,
 }
 
 #[pymethods]
 impl KmerCountTable {
     /// Constructor for KmerCountTable
+    ///
+    /// If `scaled` is set, the table acts as a FracMinHash sketch: a
+    /// hash `h` is only counted if `h <= u64::MAX / scaled`, retaining
+    /// roughly `1/scaled` of hash space. This lets differently-sized
+    /// datasets be compared via `containment` without storing every hash.
+    ///
+    /// `encoding` selects the alphabet k-mers are hashed in: `"dna"`
+    /// (default), `"protein"`, `"dayhoff"`, or `"hp"` (hydrophobic-polar).
+    /// Only `"dna"` is double-stranded and canonicalized by reverse
+    /// complement; the amino-acid alphabets are hashed as-is. `store_kmers`
+    /// is DNA-only, since the stored k-mer is 2-bit packed.
     #[new]
-    #[pyo3(signature = (ksize, store_kmers=false))]
-    pub fn new(ksize: u8, store_kmers: bool) -> Self {
+    #[pyo3(signature = (ksize, store_kmers=false, scaled=None, encoding="dna"))]
+    pub fn new(
+        ksize: u8,
+        store_kmers: bool,
+        scaled: Option<u64>,
+        encoding: &str,
+    ) -> PyResult<Self> {
+        let encoding = Encoding::parse(encoding)?;
+        if store_kmers && !encoding.is_nucleotide() {
+            return Err(PyValueError::new_err(
+                "store_kmers is only supported for the dna encoding",
+            ));
+        }
+
         // Optional init HashMap for tracking hash:kmer pairs
         let hash_to_kmer = if store_kmers {
             Some(HashMap::new())
@@ -51,14 +450,235 @@ impl KmerCountTable {
             None
         };
         // Init new KmerCountTable
-        Self {
-            counts: HashMap::new(),
+        Ok(Self {
+            backend: CountBackend::Exact(HashMap::new()),
             ksize,
             version: VERSION.to_string(), // Initialize the version field
             consumed: 0,                  // Initialize the total sequence length tracker
             store_kmers,
             hash_to_kmer,
+            hll: HllSketch::new(HLL_P),
+            scaled,
+            encoding,
+            presence: RoaringTreemap::new(),
+        })
+    }
+
+    /// Construct a `KmerCountTable` backed by a Count-Min Sketch instead
+    /// of an exact hash map, for bounded-memory counting of huge read
+    /// sets. `width` is the minimum number of slots per row (each row is
+    /// sized to a distinct prime between `width` and about `2 * width`)
+    /// and `depth` the number of independent rows; memory is `O(width * depth)`
+    /// regardless of how many distinct k-mers are counted, at the cost
+    /// of (one-sided) overcounting from hash collisions. See `new` for
+    /// `encoding`.
+    #[staticmethod]
+    #[pyo3(signature = (ksize, width, depth, store_kmers=false, scaled=None, encoding="dna"))]
+    pub fn new_cms(
+        ksize: u8,
+        width: usize,
+        depth: usize,
+        store_kmers: bool,
+        scaled: Option<u64>,
+        encoding: &str,
+    ) -> PyResult<Self> {
+        let encoding = Encoding::parse(encoding)?;
+        if store_kmers && !encoding.is_nucleotide() {
+            return Err(PyValueError::new_err(
+                "store_kmers is only supported for the dna encoding",
+            ));
+        }
+
+        let hash_to_kmer = if store_kmers {
+            Some(HashMap::new())
+        } else {
+            None
+        };
+        Ok(Self {
+            backend: CountBackend::Cms(CmsTable::new(width, depth)),
+            ksize,
+            version: VERSION.to_string(),
+            consumed: 0,
+            store_kmers,
+            hash_to_kmer,
+            hll: HllSketch::new(HLL_P),
+            scaled,
+            encoding,
+            presence: RoaringTreemap::new(),
+        })
+    }
+
+    /// For a Count-Min Sketch table, the estimated one-sided relative
+    /// overcount error: a query result is no more than `estimate_error()`
+    /// times the total number of counts inserted above the true count, with
+    /// probability at least `1 - e^-depth`. Errors for exact-counting
+    /// tables, which have no such bound because they don't estimate.
+    pub fn estimate_error(&self) -> PyResult<f64> {
+        match &self.backend {
+            CountBackend::Cms(cms) => Ok(cms.estimate_error()),
+            CountBackend::Exact(_) => Err(PyValueError::new_err(
+                "estimate_error is only defined for Count-Min Sketch tables (use new_cms)",
+            )),
+        }
+    }
+
+    /// The FracMinHash retention threshold: a hash `h` is kept only if
+    /// `h <= threshold`. `None` if `scaled` is unset (nothing downsampled).
+    fn scaled_threshold(&self) -> Option<u64> {
+        self.scaled.filter(|&s| s > 1).map(|s| u64::MAX / s)
+    }
+
+    /// Whether `hash` falls within this table's FracMinHash fraction.
+    fn passes_scaled(&self, hash: u64) -> bool {
+        match self.scaled_threshold() {
+            Some(threshold) => hash <= threshold,
+            None => true,
+        }
+    }
+
+    /// The coarser (larger) of two tables' `scaled` factors, mirroring
+    /// finch's `min_scale`: comparing two FracMinHash sketches is only
+    /// valid over the hash range both of them actually retained, so both
+    /// must be downsampled to whichever factor keeps the smaller fraction.
+    fn effective_scale(&self, other: &KmerCountTable) -> u64 {
+        self.scaled.unwrap_or(1).max(other.scaled.unwrap_or(1))
+    }
+
+    /// `presence`, further downsampled to `scale`, as a roaring bitmap.
+    fn presence_at_scale(&self, scale: u64) -> RoaringTreemap {
+        if scale <= 1 {
+            return self.presence.clone();
+        }
+        let threshold = u64::MAX / scale;
+        self.presence.iter().filter(|&hash| hash <= threshold).collect()
+    }
+
+    /// Increment the count for `hash` and return the new estimate, by
+    /// dispatching to whichever counting backend is in use. When `scaled`
+    /// is set, hashes above the FracMinHash threshold are not retained
+    /// and counting is a no-op (returning whatever count, if any, is
+    /// stored) -- but the hash is still fed to the HyperLogLog sketch
+    /// first, so `cardinality()` stays an estimate of the full stream's
+    /// distinct k-mers rather than just the retained fraction.
+    fn increment_hash(&mut self, hash: u64) -> u64 {
+        self.hll.add_hash(hash);
+        if !self.passes_scaled(hash) {
+            return self.lookup_hash(hash);
+        }
+        match &mut self.backend {
+            CountBackend::Exact(counts) => {
+                self.presence.insert(hash);
+                let count = counts.entry(hash).or_insert(0);
+                *count += 1;
+                *count
+            }
+            CountBackend::Cms(cms) => cms.increment(hash),
+        }
+    }
+
+    /// Look up the count estimate for `hash` without mutating the table.
+    fn lookup_hash(&self, hash: u64) -> u64 {
+        match &self.backend {
+            CountBackend::Exact(counts) => *counts.get(&hash).unwrap_or(&0),
+            CountBackend::Cms(cms) => cms.get(hash),
+        }
+    }
+
+    /// All distinct hashes observed by the table. Empty for the
+    /// Count-Min Sketch backend, which keeps no such set; callers that
+    /// need this should go through `require_exact_backend` first.
+    fn hash_keys(&self) -> HashSet<u64> {
+        match &self.backend {
+            CountBackend::Exact(counts) => counts.keys().cloned().collect(),
+            CountBackend::Cms(_) => HashSet::new(),
+        }
+    }
+
+    /// The number of distinct hashes observed by the table. Always 0 for
+    /// the Count-Min Sketch backend; use `cardinality` for an estimate.
+    fn num_hashes(&self) -> usize {
+        match &self.backend {
+            CountBackend::Exact(counts) => counts.len(),
+            CountBackend::Cms(_) => 0,
+        }
+    }
+
+    /// All (hash, count) pairs observed by the table. Empty for the
+    /// Count-Min Sketch backend; see `hash_keys`.
+    fn hash_count_pairs(&self) -> Vec<(u64, u64)> {
+        match &self.backend {
+            CountBackend::Exact(counts) => counts.iter().map(|(&h, &c)| (h, c)).collect(),
+            CountBackend::Cms(_) => Vec::new(),
+        }
+    }
+
+    /// All count values observed by the table (for histo/min/max/sum).
+    /// Empty for the Count-Min Sketch backend; see `hash_keys`.
+    fn count_values(&self) -> Vec<u64> {
+        match &self.backend {
+            CountBackend::Exact(counts) => counts.values().cloned().collect(),
+            CountBackend::Cms(_) => Vec::new(),
+        }
+    }
+
+    /// Error out with a clear message if this table uses the Count-Min
+    /// Sketch backend, for operations that require enumerating which
+    /// hashes were observed. The Cms backend intentionally keeps no such
+    /// set (see the `CmsTable` doc comment), so these operations aren't
+    /// available for it, same as khmer's own Count-Min-based tables.
+    fn require_exact_backend(&self, feature: &str) -> PyResult<()> {
+        match &self.backend {
+            CountBackend::Exact(_) => Ok(()),
+            CountBackend::Cms(_) => Err(PyValueError::new_err(format!(
+                "{} is not supported for Count-Min Sketch tables (built with new_cms): \
+                 they keep only approximate counts, not an exact set of observed hashes. \
+                 Use `cardinality` for an approximate distinct k-mer count, or build the \
+                 table with `new` instead.",
+                feature
+            ))),
+        }
+    }
+
+    /// Like `require_exact_backend`, but for operations comparing two tables.
+    fn require_exact_backends(&self, other: &KmerCountTable, feature: &str) -> PyResult<()> {
+        self.require_exact_backend(feature)?;
+        other.require_exact_backend(feature)
+    }
+
+    /// Remove a hash from the table, returning whether it was present.
+    /// Always returns `false` for the Count-Min Sketch backend, which
+    /// shares counter slots across hashes and so can't undo a single
+    /// hash's contribution.
+    fn remove_hash(&mut self, hash: u64) -> bool {
+        let removed = match &mut self.backend {
+            CountBackend::Exact(counts) => counts.remove(&hash).is_some(),
+            CountBackend::Cms(_) => return false,
+        };
+        if removed {
+            self.presence.remove(hash);
         }
+        removed
+    }
+
+    /// Set the count for a hash directly (used by `__setitem__`). Only the
+    /// `Exact` backend's presence set is updated; the Cms backend keeps
+    /// no such set (see `CmsTable`'s doc comment).
+    fn set_hash_count(&mut self, hash: u64, count: u64) {
+        match &mut self.backend {
+            CountBackend::Exact(counts) => {
+                self.presence.insert(hash);
+                counts.insert(hash, count);
+            }
+            CountBackend::Cms(cms) => cms.set(hash, count),
+        }
+    }
+
+    /// Rebuild `presence` from the backend's observed hashes. Used after
+    /// deserializing, since `presence` is skipped by `Serialize`/`Deserialize`.
+    /// A no-op for the Count-Min Sketch backend, which keeps `presence`
+    /// empty (see `CmsTable`'s doc comment).
+    fn rebuild_presence(&mut self) {
+        self.presence = self.hash_keys().into_iter().collect();
     }
 
     /// Turn a k-mer into a hashval.
@@ -70,8 +690,8 @@ impl KmerCountTable {
                 kmer.as_bytes(),
                 self.ksize.into(),
                 false,
-                false,
-                HashFunctions::Murmur64Dna,
+                !self.encoding.is_nucleotide(),
+                self.encoding.hash_function(),
                 42,
             );
 
@@ -84,7 +704,7 @@ impl KmerCountTable {
     pub fn unhash(&self, hash: u64) -> PyResult<String> {
         if self.store_kmers {
             if let Some(kmer) = self.hash_to_kmer.as_ref().unwrap().get(&hash) {
-                Ok(kmer.clone())
+                Ok(kmer.decode())
             } else {
                 // Raise KeyError if hash does not exist
                 let msg = format!("Warning: Hash {} not found in table.", hash);
@@ -98,9 +718,7 @@ impl KmerCountTable {
 
     /// Increment the count of a hashval by 1.
     pub fn count_hash(&mut self, hashval: u64) -> u64 {
-        let count = self.counts.entry(hashval).or_insert(0);
-        *count += 1;
-        *count
+        self.increment_hash(hashval)
     }
 
     /// Return the canonical form of a k-mer: the lexicographically smaller of the k-mer or its reverse complement.
@@ -153,13 +771,12 @@ impl KmerCountTable {
             self.consumed += kmer.len() as u64; // Add kmer len to total consumed bases
 
             if self.store_kmers {
-                // Get the canonical k-mer
+                // Get the canonical k-mer and pack it before storing
                 let canonical_kmer = self.canon(&kmer)?;
-                // Optional: Store hash:kmer pair
                 self.hash_to_kmer
                     .as_mut()
                     .unwrap()
-                    .insert(hashval, canonical_kmer);
+                    .insert(hashval, PackedKmer::encode(&canonical_kmer));
             }
 
             Ok(count) // Return the current total count for the hash
@@ -175,16 +792,16 @@ impl KmerCountTable {
         } else {
             let hashval = self.hash_kmer(kmer).expect("error hashing this k-mer");
 
-            let count = self.counts.get(&hashval).unwrap_or(&0);
+            let count = self.lookup_hash(hashval);
             debug!("get: hashval {}, count {}", hashval, count);
-            Ok(*count)
+            Ok(count)
         }
     }
 
     /// Get the count for a specific hash value directly
     pub fn get_hash(&self, hashval: u64) -> u64 {
         // Return the count for the hash value, or 0 if it does not exist
-        *self.counts.get(&hashval).unwrap_or(&0)
+        self.lookup_hash(hashval)
     }
 
     /// Get counts for a list of hashvals and return a list of counts
@@ -195,10 +812,11 @@ impl KmerCountTable {
 
     /// Drop a k-mer from the count table by its string representation
     pub fn drop(&mut self, kmer: String) -> PyResult<()> {
+        self.require_exact_backend("drop")?;
         // Compute the hash of the k-mer using the same method used for counting
         let hashval = self.hash_kmer(kmer)?;
-        // Attempt to remove the k-mer's hash from the counts HashMap
-        if self.counts.remove(&hashval).is_some() {
+        // Attempt to remove the k-mer's hash from the table
+        if self.remove_hash(hashval) {
             // If the k-mer was successfully removed, return Ok
             debug!("K-mer with hashval {} removed from table", hashval);
             Ok(())
@@ -211,8 +829,9 @@ impl KmerCountTable {
 
     /// Drop a k-mer from the count table by its hash value
     pub fn drop_hash(&mut self, hashval: u64) -> PyResult<()> {
-        // Attempt to remove the hash value from the counts HashMap
-        if self.counts.remove(&hashval).is_some() {
+        self.require_exact_backend("drop_hash")?;
+        // Attempt to remove the hash value from the table
+        if self.remove_hash(hashval) {
             // If the hash value was successfully removed, log and return Ok
             debug!("Hash value {} removed from table", hashval);
             Ok(())
@@ -225,20 +844,21 @@ impl KmerCountTable {
 
     /// Remove all k-mers with counts less than a given threshold
     pub fn mincut(&mut self, min_count: u64) -> PyResult<u64> {
+        self.require_exact_backend("mincut")?;
         // Create a vector to store the keys (hashes) to be removed
 
         let mut to_remove = Vec::new();
 
-        // Iterate over the HashMap and identify keys with counts less than the threshold
-        for (&hash, &count) in self.counts.iter() {
+        // Identify hashes with counts less than the threshold
+        for (hash, count) in self.hash_count_pairs() {
             if count < min_count {
                 to_remove.push(hash);
             }
         }
 
-        // Remove the identified keys from the counts HashMap
+        // Remove the identified hashes from the table
         for &hash in &to_remove {
-            self.counts.remove(&hash);
+            self.remove_hash(hash);
         }
 
         // Return the number of k-mers removed
@@ -247,19 +867,20 @@ impl KmerCountTable {
 
     /// Remove all k-mers with counts greater than a given threshold
     pub fn maxcut(&mut self, max_count: u64) -> PyResult<u64> {
+        self.require_exact_backend("maxcut")?;
         // Create a vector to store the keys (hashes) to be removed
         let mut to_remove = Vec::new();
 
-        // Iterate over the HashMap and identify keys with counts greater than the threshold
-        for (&hash, &count) in self.counts.iter() {
+        // Identify hashes with counts greater than the threshold
+        for (hash, count) in self.hash_count_pairs() {
             if count > max_count {
                 to_remove.push(hash);
             }
         }
 
-        // Remove the identified keys from the counts HashMap
+        // Remove the identified hashes from the table
         for &hash in &to_remove {
-            self.counts.remove(&hash);
+            self.remove_hash(hash);
         }
 
         // Return the number of k-mers removed
@@ -293,8 +914,21 @@ impl KmerCountTable {
     }
 
     #[staticmethod]
-    /// Load a KmerCountTable from a compressed file using Niffler.
+    /// Load a KmerCountTable previously written by `save` or `save_binary`.
+    /// Dispatches on the leading format tag (see `BINARY_MAGIC`), so
+    /// callers don't need to know ahead of time which format a file uses.
     pub fn load(filepath: &str) -> Result<KmerCountTable> {
+        // Peek at the leading bytes to check for the binary format tag.
+        let mut magic = [0u8; 4];
+        let has_binary_magic = File::open(filepath)
+            .and_then(|mut f| f.read_exact(&mut magic))
+            .map(|_| magic == *BINARY_MAGIC)
+            .unwrap_or(false);
+
+        if has_binary_magic {
+            return Self::load_binary(filepath);
+        }
+
         // Open the file for reading
         let file = File::open(filepath)?;
 
@@ -307,8 +941,93 @@ impl KmerCountTable {
         reader.read_to_string(&mut decompressed_data)?;
 
         // Deserialize the JSON string to a KmerCountTable
-        let loaded_table: KmerCountTable = serde_json::from_str(&decompressed_data)
+        let mut loaded_table: KmerCountTable = serde_json::from_str(&decompressed_data)
             .map_err(|e| anyhow::anyhow!("Deserialization error: {}", e))?;
+        loaded_table.rebuild_presence();
+
+        // Check version compatibility and issue a warning if necessary
+        if loaded_table.version != VERSION {
+            eprintln!(
+                "Version mismatch: loaded version is {}, but current version is {}",
+                loaded_table.version, VERSION
+            );
+        }
+
+        Ok(loaded_table)
+    }
+
+    /// Save the KmerCountTable as a binary blob, skipping the JSON
+    /// stringify/gzip round-trip `save` does. Intended for tables with
+    /// tens of millions of entries, where `save`/`load` become slow and
+    /// memory-hungry.
+    ///
+    /// The on-disk layout is `BINARY_MAGIC` + bincode-encoded struct (which
+    /// already carries `ksize`, `consumed`, and everything else needed to
+    /// reconstruct the table) + a trailing CRC32 checksum over the
+    /// bincode bytes, following the same header/payload/checksum shape
+    /// khmer uses for its binary count tables so truncated or corrupted
+    /// files are caught on `load_binary` instead of silently loading bad
+    /// counts.
+    pub fn save_binary(&self, filepath: &str) -> PyResult<()> {
+        let file = File::create(filepath).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        let mut writer = BufWriter::new(file);
+
+        let payload = bincode::serialize(self)
+            .map_err(|e| PyIOError::new_err(format!("binary serialization error: {}", e)))?;
+        let checksum = crc32fast::hash(&payload);
+
+        writer
+            .write_all(BINARY_MAGIC)
+            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        writer
+            .write_all(&payload)
+            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        writer
+            .write_all(&checksum.to_le_bytes())
+            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+        Ok(())
+    }
+
+    #[staticmethod]
+    /// Load a KmerCountTable saved with `save_binary`. Errors if the file
+    /// does not start with `BINARY_MAGIC`, is too short to hold a CRC32
+    /// trailer, or the trailer doesn't match the payload (a corrupt or
+    /// truncated file); use `load` if the format of a file isn't known
+    /// ahead of time.
+    pub fn load_binary(filepath: &str) -> Result<KmerCountTable> {
+        let mut file = File::open(filepath)?;
+
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        if magic != *BINARY_MAGIC {
+            return Err(anyhow!(
+                "file does not start with the oxli binary format tag"
+            ));
+        }
+
+        let mut rest = Vec::new();
+        file.read_to_end(&mut rest)?;
+        if rest.len() < 4 {
+            return Err(anyhow!(
+                "file is truncated: missing trailing CRC32 checksum"
+            ));
+        }
+        let split = rest.len() - 4;
+        let (payload, checksum_bytes) = rest.split_at(split);
+        let expected_checksum = u32::from_le_bytes(checksum_bytes.try_into().unwrap());
+        let actual_checksum = crc32fast::hash(payload);
+        if actual_checksum != expected_checksum {
+            return Err(anyhow!(
+                "checksum mismatch: file is corrupt or truncated (expected {:08x}, got {:08x})",
+                expected_checksum,
+                actual_checksum
+            ));
+        }
+
+        let mut loaded_table: KmerCountTable = bincode::deserialize(payload)
+            .map_err(|e| anyhow!("binary deserialization error: {}", e))?;
+        loaded_table.rebuild_presence();
 
         // Check version compatibility and issue a warning if necessary
         if loaded_table.version != VERSION {
@@ -334,6 +1053,8 @@ impl KmerCountTable {
         sortcounts: bool,
         sortkeys: bool,
     ) -> PyResult<Vec<(u64, u64)>> {
+        self.require_exact_backend("dump")?;
+
         // Raise an error if both sortcounts and sortkeys are true
         if sortcounts && sortkeys {
             return Err(PyValueError::new_err(
@@ -342,16 +1063,16 @@ impl KmerCountTable {
         }
 
         // Collect hashes and counts
-        let mut hash_count_pairs: Vec<(&u64, &u64)> = self.counts.iter().collect();
+        let mut hash_count_pairs: Vec<(u64, u64)> = self.hash_count_pairs();
 
         // Handle sorting based on the flags
         if sortkeys {
             // Sort by hash keys if `sortkeys` is set to true
-            hash_count_pairs.sort_by_key(|&(hash, _)| *hash);
+            hash_count_pairs.sort_by_key(|&(hash, _)| hash);
         } else if sortcounts {
             // Sort by count, secondary sort by hash if `sortcounts` is true
             hash_count_pairs.sort_by(|&(hash1, count1), &(hash2, count2)| {
-                count1.cmp(count2).then_with(|| hash1.cmp(hash2))
+                count1.cmp(&count2).then_with(|| hash1.cmp(&hash2))
             });
         }
         // If both sortcounts and sortkeys are false, no sorting is done.
@@ -369,14 +1090,8 @@ impl KmerCountTable {
             writer.flush()?; // Flush the buffer
             Ok(vec![]) // Return empty vector to Python
         } else {
-            // Convert the vector of references to owned values
-            let result: Vec<(u64, u64)> = hash_count_pairs
-                .into_iter()
-                .map(|(&hash, &count)| (hash, count))
-                .collect();
-
             // Return the vector of (hash, count) tuples
-            Ok(result)
+            Ok(hash_count_pairs)
         }
     }
 
@@ -407,26 +1122,29 @@ impl KmerCountTable {
             ));
         }
 
-        // Collect canonical k-mers and their counts, skipping those not found in the counts table
-        let mut kmer_count_pairs: Vec<(&String, &u64)> = self
+        // Look each stored k-mer's count up directly, rather than
+        // pre-building a hash->count map from `hash_count_pairs()` (which
+        // only the `Exact` backend can enumerate): `hash_to_kmer` already
+        // holds exactly the hashes we care about regardless of backend,
+        // and `lookup_hash` works against either one.
+        let mut kmer_count_pairs: Vec<(&PackedKmer, u64)> = self
             .hash_to_kmer
             .as_ref()
             .unwrap()
             .par_iter() // Use rayon for parallel iteration
-            .filter_map(|(&hash, kmer)| {
-                // Use filter_map to only include (kmer, count) pairs where the count exists
-                self.counts.get(&hash).map(|count| (kmer, count))
-            })
+            .map(|(&hash, kmer)| (kmer, self.lookup_hash(hash)))
             .collect();
 
-        // Handle sorting based on the flags
+        // Handle sorting based on the flags. Sorting the packed (integer)
+        // representation is cheaper than sorting decoded strings, and
+        // agrees with lexicographic order since bases are packed MSB-first.
         if sortkeys {
             // Sort by canonical kmer lexicographically
-            kmer_count_pairs.par_sort_by_key(|&(kmer, _)| kmer.clone());
+            kmer_count_pairs.par_sort_by_key(|&(kmer, _)| *kmer);
         } else if sortcounts {
             // Sort by count, secondary sort by kmer
             kmer_count_pairs.par_sort_by(|&(kmer1, count1), &(kmer2, count2)| {
-                count1.cmp(count2).then_with(|| kmer1.cmp(kmer2))
+                count1.cmp(&count2).then_with(|| kmer1.cmp(kmer2))
             });
         }
         // If both sortcounts and sortkeys are false, no sorting is done.
@@ -436,18 +1154,18 @@ impl KmerCountTable {
             let f = File::create(filepath)?;
             let mut writer = BufWriter::new(f);
 
-            // Write each kmer:count pair to the file
+            // Write each kmer:count pair to the file, decoding on the way out
             for (kmer, count) in kmer_count_pairs {
-                writeln!(writer, "{}\t{}", kmer, count)?;
+                writeln!(writer, "{}\t{}", kmer.decode(), count)?;
             }
 
             writer.flush()?; // Ensure all data is written to the file
             Ok(vec![]) // Return an empty vector when writing to a file
         } else {
-            // Convert the vector of references to owned values
+            // Decode the packed k-mers to owned strings for the Python side
             let result: Vec<(String, u64)> = kmer_count_pairs
                 .into_par_iter() // Use rayon for parallel conversion
-                .map(|(kmer, &count)| (kmer.clone(), count))
+                .map(|(kmer, count)| (kmer.decode(), count))
                 .collect();
 
             // Return the vector of (kmer, count) tuples
@@ -462,11 +1180,13 @@ impl KmerCountTable {
     /// If `zero` is True, include all frequencies from 0 to max observed count,
     /// even if no k-mers were observed for those frequencies.
     #[pyo3(signature = (zero=true))]
-    pub fn histo(&self, zero: bool) -> Vec<(u64, u64)> {
+    pub fn histo(&self, zero: bool) -> PyResult<Vec<(u64, u64)>> {
+        self.require_exact_backend("histo")?;
+
         let mut freq_count: HashMap<u64, u64> = HashMap::new();
 
         // Step 1: Count the frequencies of observed k-mer counts
-        for &count in self.counts.values() {
+        for count in self.count_values() {
             *freq_count.entry(count).or_insert(0) += 1;
         }
 
@@ -474,7 +1194,7 @@ impl KmerCountTable {
 
         if zero {
             // Step 2 (optional): Include all frequencies from 0 to max_count
-            let max_count = self.max();
+            let max_count = self.max()?;
             histo_vec = (0..=max_count)
                 .map(|freq| (freq, *freq_count.get(&freq).unwrap_or(&0)))
                 .collect();
@@ -484,40 +1204,37 @@ impl KmerCountTable {
             histo_vec.sort_by_key(|&(frequency, _)| frequency);
         }
 
-        histo_vec
+        Ok(histo_vec)
     }
 
     /// Finds and returns the minimum count in the counts HashMap.
-    /// Returns 0 if the HashMap is empty.
+    /// Returns 0 if the HashMap is empty. Not supported for Count-Min
+    /// Sketch tables, which keep no set of observed hashes to iterate.
     #[getter]
-    pub fn min(&self) -> u64 {
-        // Check if the HashMap is empty, return 0 if true
-        if self.counts.is_empty() {
-            return 0;
-        }
-
-        // Iterate over the counts and find the minimum value
-        *self.counts.values().min().unwrap_or(&0)
+    pub fn min(&self) -> PyResult<u64> {
+        self.require_exact_backend("min")?;
+        // Iterate over the counts and find the minimum value, 0 if empty
+        Ok(self.count_values().into_iter().min().unwrap_or(0))
     }
 
     /// Finds and returns the maximum count in the counts HashMap.
-    /// Returns 0 if the HashMap is empty.
+    /// Returns 0 if the HashMap is empty. Not supported for Count-Min
+    /// Sketch tables, which keep no set of observed hashes to iterate.
     #[getter]
-    pub fn max(&self) -> u64 {
-        // Check if the HashMap is empty, return 0 if true
-        if self.counts.is_empty() {
-            return 0;
-        }
-
-        // Iterate over the counts and find the maximum value
-        *self.counts.values().max().unwrap_or(&0)
+    pub fn max(&self) -> PyResult<u64> {
+        self.require_exact_backend("max")?;
+        // Iterate over the counts and find the maximum value, 0 if empty
+        Ok(self.count_values().into_iter().max().unwrap_or(0))
     }
 
-    // Getter for the 'hashes' attribute, returning all hash keys in the table
+    // Getter for the 'hashes' attribute, returning all hash keys in the table.
+    // Not supported for Count-Min Sketch tables, which keep no such set
+    // (use `cardinality` for an approximate distinct k-mer count instead).
     #[getter]
-    pub fn hashes(&self) -> Vec<u64> {
-        // Collect and return all keys from the counts HashMap
-        self.counts.keys().cloned().collect()
+    pub fn hashes(&self) -> PyResult<Vec<u64>> {
+        self.require_exact_backend("hashes")?;
+        // Collect and return all observed hashes
+        Ok(self.hash_keys().into_iter().collect())
     }
 
     // Attribute to access the version of oxli that the table was created with
@@ -532,10 +1249,55 @@ impl KmerCountTable {
         self.consumed
     }
 
-    // Getter for the sum of all counts in the table.
+    // Getter for the sum of all counts in the table. Not supported for
+    // Count-Min Sketch tables, which keep no set of observed hashes to sum.
+    #[getter]
+    pub fn sum_counts(&self) -> PyResult<u64> {
+        self.require_exact_backend("sum_counts")?;
+        Ok(self.count_values().into_iter().sum())
+    }
+
+    /// Estimated number of distinct k-mers ever counted, via HyperLogLog.
+    /// Unlike `len()`/`hashes`, this stays accurate even after `mincut`,
+    /// `maxcut`, or `drop` have removed hashes from the counting backend,
+    /// and even when `scaled` is set: every observed hash is fed to the
+    /// sketch before FracMinHash downsampling decides whether to retain
+    /// it, so this estimates the full stream's distinct k-mer count, not
+    /// just the retained fraction.
+    #[getter]
+    pub fn cardinality(&self) -> u64 {
+        self.hll.estimate().round() as u64
+    }
+
+    /// The configured FracMinHash downsampling factor, if any: only
+    /// hashes `h <= u64::MAX / scaled` are retained, so roughly `1/scaled`
+    /// of hash space is kept. `None` means every k-mer is retained.
+    #[getter]
+    pub fn scaled(&self) -> Option<u64> {
+        self.scaled
+    }
+
+    /// Number of distinct hashes actually retained under `scaled`
+    /// downsampling. Together with `scaled`, this bounds the estimation
+    /// error of `jaccard`/`containment` derived from this table: the
+    /// fewer hashes retained, the noisier the estimate.
+    ///
+    /// Count-Min Sketch tables keep no exact set of observed hashes, so
+    /// this falls back to the (approximate) HyperLogLog `cardinality`
+    /// estimate for them instead of erroring.
+    #[getter]
+    pub fn retained_hashes(&self) -> usize {
+        match &self.backend {
+            CountBackend::Exact(_) => self.num_hashes(),
+            CountBackend::Cms(_) => self.cardinality() as usize,
+        }
+    }
+
+    /// The alphabet this table hashes k-mers in: `"dna"`, `"protein"`,
+    /// `"dayhoff"`, or `"hp"`.
     #[getter]
-    pub fn sum_counts(&self) -> u64 {
-        self.counts.values().sum()
+    pub fn encoding(&self) -> &str {
+        self.encoding.as_str()
     }
 
     // Consume this DNA string. Return total number of k-mers consumed.
@@ -553,17 +1315,34 @@ impl KmerCountTable {
             let hash_to_kmer = self.hash_to_kmer.as_mut().unwrap();
 
             // Create an iterator for (canonical_kmer, hash) pairs
-            let iter = KmersAndHashesIter::new(seq, self.ksize as usize, skip_bad_kmers);
+            let iter = KmersAndHashesIter::new(seq, self.ksize as usize, skip_bad_kmers, self.encoding)?;
 
             // Iterate over the k-mers and their hashes
             for result in iter {
                 match result {
                     Ok((kmer, hash)) => {
                         if hash != 0 {
-                            // Insert hash:kmer pair into the hashmap
-                            hash_to_kmer.insert(hash, kmer.clone());
-                            // Increment the count for the hash
-                            *self.counts.entry(hash).or_insert(0) += 1;
+                            // Feed the HyperLogLog sketch every observed hash,
+                            // scaled-retained or not, so cardinality() estimates
+                            // the full stream's distinct k-mers, not just the
+                            // retained fraction.
+                            self.hll.add_hash(hash);
+                        }
+                        if hash != 0 && self.passes_scaled(hash) {
+                            // Insert the packed hash:kmer pair into the hashmap
+                            hash_to_kmer.insert(hash, PackedKmer::encode(&kmer));
+                            // Increment the count for the hash. Dispatched inline
+                            // (rather than via self.increment_hash) so the borrow
+                            // stays disjoint from the `hash_to_kmer` borrow above.
+                            match &mut self.backend {
+                                CountBackend::Exact(counts) => {
+                                    self.presence.insert(hash);
+                                    *counts.entry(hash).or_insert(0) += 1;
+                                }
+                                CountBackend::Cms(cms) => {
+                                    cms.increment(hash);
+                                }
+                            }
                             // Tally kmers added
                             n += 1;
                         }
@@ -577,8 +1356,8 @@ impl KmerCountTable {
                 seq.as_bytes(),
                 self.ksize.into(),
                 skip_bad_kmers,
-                false,
-                HashFunctions::Murmur64Dna,
+                !self.encoding.is_nucleotide(),
+                self.encoding.hash_function(),
                 42,
             );
 
@@ -605,64 +1384,141 @@ impl KmerCountTable {
         Ok(n)
     }
 
-    // Helper method to get hash set of k-mers
-    fn hash_set(&self) -> HashSet<u64> {
-        self.counts.keys().cloned().collect()
+    /// Estimate this sequence's coverage against the current table: the
+    /// (median, mean, min) of its k-mers' counts, without modifying the
+    /// table. This is the per-read coverage estimate khmer's digital
+    /// normalization is built on.
+    #[pyo3(signature = (seq, skip_bad_kmers=true))]
+    pub fn median_abundance(&self, seq: &str, skip_bad_kmers: bool) -> PyResult<(f64, f64, u64)> {
+        let iter = KmersAndHashesIter::new(seq, self.ksize as usize, skip_bad_kmers, self.encoding)?;
+
+        let mut counts = Vec::new();
+        for result in iter {
+            match result {
+                Ok((_, hash)) => {
+                    if hash != 0 {
+                        counts.push(self.lookup_hash(hash));
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if counts.is_empty() {
+            return Err(PyValueError::new_err(
+                "sequence is too short to contain a k-mer, or contains no valid k-mers",
+            ));
+        }
+
+        counts.sort_unstable();
+        let min = counts[0];
+        let mean = counts.iter().sum::<u64>() as f64 / counts.len() as f64;
+        let mid = counts.len() / 2;
+        let median = if counts.len() % 2 == 0 {
+            (counts[mid - 1] + counts[mid]) as f64 / 2.0
+        } else {
+            counts[mid] as f64
+        };
+
+        Ok((median, mean, min))
     }
 
-    // Set operation methods
-    pub fn union(&self, other: &KmerCountTable) -> HashSet<u64> {
-        self.hash_set().union(&other.hash_set()).cloned().collect()
+    /// Digital normalization: consume `seq` only if its median k-mer
+    /// abundance against the table's current state is below `cutoff`.
+    /// Returns whether the read was kept (and thus consumed), letting
+    /// callers stream reads through a single table while subsampling
+    /// high-coverage regions, matching khmer's `normalize-by-median`.
+    #[pyo3(signature = (seq, cutoff, skip_bad_kmers=true))]
+    pub fn consume_with_diginorm(
+        &mut self,
+        seq: &str,
+        cutoff: f64,
+        skip_bad_kmers: bool,
+    ) -> PyResult<bool> {
+        let (median, _mean, _min) = self.median_abundance(seq, skip_bad_kmers)?;
+
+        if median < cutoff {
+            self.consume(seq, skip_bad_kmers)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
     }
 
-    pub fn intersection(&self, other: &KmerCountTable) -> HashSet<u64> {
-        self.hash_set()
-            .intersection(&other.hash_set())
-            .cloned()
-            .collect()
+    /// A roaring (compressed) bitmap of every hash this table has observed,
+    /// kept in sync with the counting backend. `union`/`intersection`/
+    /// `jaccard`/`containment` are built on AND/OR/cardinality over this
+    /// bitmap rather than hashing-based `HashSet` ops, which is dramatically
+    /// faster and lower-memory at the table sizes this crate targets.
+    /// Exposed so callers can run the same fast comparisons across a whole
+    /// collection of tables without repeatedly re-deriving it.
+    ///
+    /// Not supported for Count-Min Sketch tables, which keep `presence`
+    /// empty so as not to reintroduce the unbounded memory usage the
+    /// backend exists to avoid (see `CmsTable`'s doc comment).
+    pub fn presence_bitmap(&self) -> PyResult<Vec<u64>> {
+        self.require_exact_backend("presence_bitmap")?;
+        Ok(self.presence.iter().collect())
     }
 
-    pub fn difference(&self, other: &KmerCountTable) -> HashSet<u64> {
-        self.hash_set()
-            .difference(&other.hash_set())
-            .cloned()
-            .collect()
+    // Set operation methods, via roaring bitmap AND/OR/difference instead
+    // of building and comparing `HashSet<u64>`s. Not supported for
+    // Count-Min Sketch tables; see `presence_bitmap`.
+    pub fn union(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
+        self.require_exact_backends(other, "union")?;
+        Ok((&self.presence | &other.presence).iter().collect())
     }
 
-    pub fn symmetric_difference(&self, other: &KmerCountTable) -> HashSet<u64> {
-        self.hash_set()
-            .symmetric_difference(&other.hash_set())
-            .cloned()
-            .collect()
+    pub fn intersection(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
+        self.require_exact_backends(other, "intersection")?;
+        Ok((&self.presence & &other.presence).iter().collect())
+    }
+
+    pub fn difference(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
+        self.require_exact_backends(other, "difference")?;
+        Ok((&self.presence - &other.presence).iter().collect())
+    }
+
+    pub fn symmetric_difference(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
+        self.require_exact_backends(other, "symmetric_difference")?;
+        Ok((&self.presence ^ &other.presence).iter().collect())
     }
 
     // Python dunder methods for set operations
-    fn __or__(&self, other: &KmerCountTable) -> HashSet<u64> {
+    fn __or__(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
         self.union(other)
     }
 
-    fn __and__(&self, other: &KmerCountTable) -> HashSet<u64> {
+    fn __and__(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
         self.intersection(other)
     }
 
-    fn __sub__(&self, other: &KmerCountTable) -> HashSet<u64> {
+    fn __sub__(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
         self.difference(other)
     }
 
-    fn __xor__(&self, other: &KmerCountTable) -> HashSet<u64> {
+    fn __xor__(&self, other: &KmerCountTable) -> PyResult<HashSet<u64>> {
         self.symmetric_difference(other)
     }
 
-    // Python __iter__ method to return an iterator
-    pub fn __iter__(slf: PyRef<Self>) -> KmerCountTableIterator {
-        KmerCountTableIterator {
-            inner: slf.counts.clone().into_iter(), // Clone the HashMap and convert to iterator
-        }
+    // Python __iter__ method to return an iterator. Not supported for
+    // Count-Min Sketch tables, which keep no set of observed hashes to
+    // iterate over.
+    pub fn __iter__(slf: PyRef<Self>) -> PyResult<KmerCountTableIterator> {
+        slf.require_exact_backend("iterating over a KmerCountTable")?;
+        Ok(KmerCountTableIterator {
+            inner: slf.hash_count_pairs().into_iter(),
+        })
     }
 
-    // Python dunder method for __len__
+    // Python dunder method for __len__. Falls back to the (approximate)
+    // HyperLogLog `cardinality` estimate for Count-Min Sketch tables,
+    // which keep no exact set of observed hashes to count.
     fn __len__(&self) -> usize {
-        self.counts.len()
+        match &self.backend {
+            CountBackend::Exact(_) => self.num_hashes(),
+            CountBackend::Cms(_) => self.cardinality() as usize,
+        }
     }
 
     // Python dunder method for __getitem__
@@ -675,7 +1531,7 @@ impl KmerCountTable {
         // Calculate the hash for the k-mer
         let hashval = self.hash_kmer(kmer)?;
         // Set the count for the k-mer
-        self.counts.insert(hashval, count);
+        self.set_hash_count(hashval, count);
         Ok(())
     }
 
@@ -688,7 +1544,7 @@ impl KmerCountTable {
         let mut v: Vec<(String, u64)> = vec![];
 
         // Create the iterator
-        let iter = KmersAndHashesIter::new(seq, self.ksize as usize, skip_bad_kmers);
+        let iter = KmersAndHashesIter::new(seq, self.ksize as usize, skip_bad_kmers, self.encoding)?;
 
         // Collect the k-mers and their hashes
         for result in iter {
@@ -702,53 +1558,188 @@ impl KmerCountTable {
     }
 
     /// Calculates the Jaccard Similarity Coefficient between two KmerCountTable objects.
+    ///
+    /// If either table was built with `scaled` set, both are first
+    /// downsampled to their common (coarser) scale before comparing, so
+    /// differently-scaled FracMinHash sketches remain comparable.
     /// # Returns
     /// The Jaccard Similarity Coefficient between the two tables as a float value between 0 and 1.
-    pub fn jaccard(&self, other: &KmerCountTable) -> f64 {
-        // Get the intersection of the two k-mer sets.
-        let intersection_size = self.intersection(other).len();
-
-        // Get the union of the two k-mer sets.
-        let union_size = self.union(other).len();
+    ///
+    /// Not supported for Count-Min Sketch tables, which keep no set of
+    /// observed hashes to intersect/union (see `CmsTable`'s doc comment).
+    pub fn jaccard(&self, other: &KmerCountTable) -> PyResult<f64> {
+        self.require_exact_backends(other, "jaccard")?;
+        let scale = self.effective_scale(other);
+        let self_bitmap = self.presence_at_scale(scale);
+        let other_bitmap = other.presence_at_scale(scale);
+
+        // Intersection/union cardinality via roaring bitmap AND/OR, not
+        // per-element `.contains()` lookups.
+        let intersection_size = (&self_bitmap & &other_bitmap).len();
+        let union_size = (&self_bitmap | &other_bitmap).len();
 
         // Handle the case where the union is empty (both sets are empty).
         if union_size == 0 {
-            return 1.0; // By convention, two empty sets are considered identical.
+            return Ok(1.0); // By convention, two empty sets are considered identical.
         }
 
         // Calculate and return the Jaccard similarity as a ratio of intersection to union.
-        intersection_size as f64 / union_size as f64
+        Ok(intersection_size as f64 / union_size as f64)
+    }
+
+    /// Containment of `self` within `other`: the fraction of `self`'s
+    /// retained hashes that also appear in `other`. Unlike `jaccard`, this
+    /// is asymmetric, which makes it useful for comparing a small sketch
+    /// against a much larger one (e.g. a genome bin against a metagenome)
+    /// without the large table's extra hashes diluting the score.
+    /// # Returns
+    /// `|self ∩ other| / |self|`, estimated from retained (post-`scaled`) hashes.
+    ///
+    /// Not supported for Count-Min Sketch tables; see `jaccard`.
+    pub fn containment(&self, other: &KmerCountTable) -> PyResult<f64> {
+        self.require_exact_backends(other, "containment")?;
+        let scale = self.effective_scale(other);
+        let self_bitmap = self.presence_at_scale(scale);
+
+        if self_bitmap.is_empty() {
+            return Ok(0.0);
+        }
+
+        let other_bitmap = other.presence_at_scale(scale);
+        let shared = (&self_bitmap & &other_bitmap).len();
+
+        Ok(shared as f64 / self_bitmap.len() as f64)
+    }
+
+    /// Bottom-n MinHash sketch: the `n` smallest hashes in the table.
+    /// Cheap to compare across tables without materializing full hash sets.
+    /// Not supported for Count-Min Sketch tables, which keep no set of
+    /// observed hashes to sort (see `CmsTable`'s doc comment).
+    pub fn sketch(&self, n: usize) -> PyResult<BTreeSet<u64>> {
+        self.require_exact_backend("sketch")?;
+        let mut hashes: Vec<u64> = self.hash_keys().into_iter().collect();
+        hashes.sort_unstable();
+        hashes.truncate(n);
+        Ok(hashes.into_iter().collect())
+    }
+
+    /// Estimate the Jaccard index between two tables from their bottom-n
+    /// sketches, at O(n) cost instead of O(table size). Merges the two
+    /// bottom-n sketches, takes the `n` smallest hashes of the union, and
+    /// reports the fraction of those present in both original sketches.
+    /// Not supported for Count-Min Sketch tables; see `sketch`.
+    pub fn similarity(&self, other: &KmerCountTable, n: usize) -> PyResult<f64> {
+        let sketch_self = self.sketch(n)?;
+        let sketch_other = other.sketch(n)?;
+
+        let mut merged: Vec<u64> = sketch_self
+            .iter()
+            .chain(sketch_other.iter())
+            .cloned()
+            .collect();
+        merged.sort_unstable();
+        merged.dedup();
+        merged.truncate(n);
+
+        if merged.is_empty() {
+            return Ok(1.0); // By convention, two empty sketches are identical.
+        }
+
+        let shared = merged
+            .iter()
+            .filter(|hash| sketch_self.contains(hash) && sketch_other.contains(hash))
+            .count();
+
+        Ok(shared as f64 / merged.len() as f64)
+    }
+
+    /// Abundance-weighted (cosine) variant of `similarity`, using the
+    /// stored counts of the hashes in the merged bottom-n sketch rather
+    /// than just their presence/absence. Not supported for Count-Min
+    /// Sketch tables; see `sketch`.
+    pub fn weighted_similarity(&self, other: &KmerCountTable, n: usize) -> PyResult<f64> {
+        let sketch_self = self.sketch(n)?;
+        let sketch_other = other.sketch(n)?;
+
+        let mut merged: Vec<u64> = sketch_self
+            .iter()
+            .chain(sketch_other.iter())
+            .cloned()
+            .collect();
+        merged.sort_unstable();
+        merged.dedup();
+        merged.truncate(n);
+
+        let dot_product: f64 = merged
+            .iter()
+            .map(|&hash| self.lookup_hash(hash) as f64 * other.lookup_hash(hash) as f64)
+            .sum();
+
+        let magnitude_self: f64 = merged
+            .iter()
+            .map(|&hash| (self.lookup_hash(hash) as f64).powi(2))
+            .sum::<f64>()
+            .sqrt();
+
+        let magnitude_other: f64 = merged
+            .iter()
+            .map(|&hash| (other.lookup_hash(hash) as f64).powi(2))
+            .sum::<f64>()
+            .sqrt();
+
+        if magnitude_self == 0.0 || magnitude_other == 0.0 {
+            return Ok(0.0);
+        }
+
+        Ok(dot_product / (magnitude_self * magnitude_other))
     }
 
     /// Cosine similarity between two `KmerCountTable` objects.
+    ///
+    /// If either table was built with `scaled` set, both are first
+    /// downsampled to their common (coarser) scale before comparing, so
+    /// differently-scaled FracMinHash sketches remain comparable.
     /// # Returns
     /// The cosine similarity between the two tables as a float value between 0 and 1.
-    pub fn cosine(&self, other: &KmerCountTable) -> f64 {
+    ///
+    /// Not supported for Count-Min Sketch tables, which keep no set of
+    /// observed hashes to compare (see `CmsTable`'s doc comment).
+    pub fn cosine(&self, other: &KmerCountTable) -> PyResult<f64> {
+        self.require_exact_backends(other, "cosine")?;
+        let scale = self.effective_scale(other);
+        let threshold = u64::MAX / scale.max(1);
+        let keep = |&(hash, _): &(u64, u64)| scale <= 1 || hash <= threshold;
+
+        let self_counts: Vec<(u64, u64)> =
+            self.hash_count_pairs().into_iter().filter(keep).collect();
+        let other_counts: HashMap<u64, u64> = other
+            .hash_count_pairs()
+            .into_iter()
+            .filter(keep)
+            .collect();
+
         // Early return if either table is empty.
-        if self.counts.is_empty() || other.counts.is_empty() {
-            return 0.0;
+        if self_counts.is_empty() || other_counts.is_empty() {
+            return Ok(0.0);
         }
 
         // Calculate the dot product in parallel.
-        let dot_product: u64 = self
-            .counts
+        let dot_product: u64 = self_counts
             .par_iter()
-            .filter_map(|(&hash, &count1)| {
+            .filter_map(|&(hash, count1)| {
                 // Only include in the dot product if both tables have the k-mer.
-                other.counts.get(&hash).map(|&count2| count1 * count2)
+                other_counts.get(&hash).map(|&count2| count1 * count2)
             })
             .sum();
 
         // Calculate magnitudes in parallel for both tables.
-        let magnitude_self: f64 = self
-            .counts
+        let magnitude_self: f64 = self_counts
             .par_iter()
-            .map(|(_, v)| (*v as f64).powi(2)) // Access the value, square it
+            .map(|&(_, v)| (v as f64).powi(2)) // Access the value, square it
             .sum::<f64>()
             .sqrt();
 
-        let magnitude_other: f64 = other
-            .counts
+        let magnitude_other: f64 = other_counts
             .par_iter()
             .map(|(_, v)| (*v as f64).powi(2)) // Access the value, square it
             .sum::<f64>()
@@ -756,11 +1747,69 @@ impl KmerCountTable {
 
         // If either magnitude is zero (no k-mers), return 0 to avoid division by zero.
         if magnitude_self == 0.0 || magnitude_other == 0.0 {
-            return 0.0;
+            return Ok(0.0);
         }
 
         // Calculate and return cosine similarity.
-        dot_product as f64 / (magnitude_self * magnitude_other)
+        Ok(dot_product as f64 / (magnitude_self * magnitude_other))
+    }
+
+    /// Angular distance: `acos(cosine) / pi`, a true metric (unlike cosine
+    /// similarity itself, it satisfies the triangle inequality) on the
+    /// nonnegative abundance vectors `cosine` compares.
+    /// # Returns
+    /// A value in `[0, 1]`, where 0 means identical abundance profiles.
+    ///
+    /// Not supported for Count-Min Sketch tables; see `cosine`.
+    pub fn angular_distance(&self, other: &KmerCountTable) -> PyResult<f64> {
+        let cosine = self.cosine(other)?.clamp(-1.0, 1.0);
+        Ok(cosine.acos() / std::f64::consts::PI)
+    }
+
+    /// Bray-Curtis dissimilarity between two tables' abundance profiles:
+    /// `1 - 2*sum(min(a_i,b_i)) / (sum(a_i)+sum(b_i))` over the union of
+    /// observed hashes, following the same scale-downsampling and
+    /// shared/unique-hash iteration as `cosine`.
+    /// # Returns
+    /// A value in `[0, 1]`, where 0 means identical abundance profiles and
+    /// 1 means no shared abundance at all.
+    ///
+    /// Not supported for Count-Min Sketch tables; see `cosine`.
+    pub fn bray_curtis(&self, other: &KmerCountTable) -> PyResult<f64> {
+        self.require_exact_backends(other, "bray_curtis")?;
+        let scale = self.effective_scale(other);
+        let threshold = u64::MAX / scale.max(1);
+        let keep = |&(hash, _): &(u64, u64)| scale <= 1 || hash <= threshold;
+
+        let self_counts: Vec<(u64, u64)> =
+            self.hash_count_pairs().into_iter().filter(keep).collect();
+        let other_counts: HashMap<u64, u64> = other
+            .hash_count_pairs()
+            .into_iter()
+            .filter(keep)
+            .collect();
+
+        let sum_self: u64 = self_counts.par_iter().map(|&(_, count)| count).sum();
+        let sum_other: u64 = other_counts.values().sum();
+        let total = sum_self + sum_other;
+
+        // Two tables with no abundance at all are identical by convention.
+        if total == 0 {
+            return Ok(0.0);
+        }
+
+        // Sum of per-hash minimum abundance, over hashes present in `self`
+        // (hashes only in `other` contribute a minimum of 0).
+        let shared_min: u64 = self_counts
+            .par_iter()
+            .map(|&(hash, count1)| {
+                other_counts
+                    .get(&hash)
+                    .map_or(0, |&count2| count1.min(count2))
+            })
+            .sum();
+
+        Ok(1.0 - (2.0 * shared_min as f64) / total as f64)
     }
 
     /// Add counts from another KmerCountTable to this one.
@@ -781,30 +1830,81 @@ impl KmerCountTable {
                 "KmerCountTables must have the same ksize",
             ));
         }
+        if self.encoding != other.encoding {
+            return Err(PyValueError::new_err(
+                "KmerCountTables must have the same encoding",
+            ));
+        }
+        if self.scaled != other.scaled {
+            return Err(PyValueError::new_err(
+                "KmerCountTables must have the same scaled value",
+            ));
+        }
 
-        let total_counts_added = AtomicU64::new(0);
-        let new_keys_added = AtomicU64::new(0);
-        let counts_mutex = Mutex::new(&mut self.counts);
+        let (total_added, new_keys) = match (&mut self.backend, &other.backend) {
+            (CountBackend::Exact(counts), CountBackend::Exact(other_counts)) => {
+                let total_counts_added = AtomicU64::new(0);
+                let new_keys_added = AtomicU64::new(0);
+                let counts_mutex = Mutex::new(counts);
 
-        // Use thread-local storage to collect updates
-        let updates: Vec<_> = other
-            .counts
-            .par_iter()
-            .map(|(&hash, &count)| (hash, count))
-            .collect();
+                // Use thread-local storage to collect updates
+                let updates: Vec<_> = other_counts
+                    .par_iter()
+                    .map(|(&hash, &count)| (hash, count))
+                    .collect();
+
+                // Apply updates in parallel
+                updates.par_iter().for_each(|(hash, count)| {
+                    let mut counts_lock = counts_mutex.lock().unwrap();
+                    let current_count = counts_lock.entry(*hash).or_insert(0);
+                    if *current_count == 0 {
+                        new_keys_added.fetch_add(1, Ordering::Relaxed);
+                    }
+                    *current_count += count;
+                    total_counts_added.fetch_add(*count, Ordering::Relaxed);
+                });
+
+                (
+                    total_counts_added.load(Ordering::Relaxed),
+                    new_keys_added.load(Ordering::Relaxed),
+                )
+            }
+            (CountBackend::Cms(cms), CountBackend::Cms(other_cms)) => {
+                // Count-Min Sketch tables merge by summing slots directly,
+                // which is equivalent to (and much cheaper than) replaying
+                // every increment from the other table.
+                if cms.widths != other_cms.widths {
+                    return Err(PyValueError::new_err(
+                        "Count-Min Sketch tables must have identical geometry to merge",
+                    ));
+                }
 
-        // Apply updates in parallel
-        updates.par_iter().for_each(|(hash, count)| {
-            let mut counts_lock = counts_mutex.lock().unwrap();
-            let current_count = counts_lock.entry(*hash).or_insert(0);
-            if *current_count == 0 {
-                new_keys_added.fetch_add(1, Ordering::Relaxed);
+                for row in 0..cms.depth {
+                    for slot in 0..cms.widths[row] {
+                        cms.tables[row][slot] =
+                            cms.tables[row][slot].saturating_add(other_cms.tables[row][slot]);
+                    }
+                }
+
+                // Row 0's slots sum to exactly the total count mass the
+                // other table ever added (every increment touches each row
+                // exactly once), so this is exact even though individual
+                // per-hash counts are only approximate. "New keys" isn't
+                // meaningful here: Cms tables keep no seen-set to tell a
+                // new hash from one already present (see `CmsTable`).
+                let total_added: u64 = other_cms.tables[0].iter().map(|&c| c as u64).sum();
+
+                (total_added, 0)
+            }
+            _ => {
+                return Err(PyValueError::new_err(
+                    "KmerCountTables must use the same counting backend to merge",
+                ))
             }
-            *current_count += count;
-            total_counts_added.fetch_add(*count, Ordering::Relaxed);
-        });
+        };
 
         self.consumed += other.consumed;
+        self.hll.merge(&other.hll);
 
         if self.store_kmers {
             if other.store_kmers {
@@ -826,8 +1926,9 @@ impl KmerCountTable {
             }
         }
 
-        let total_added = total_counts_added.load(Ordering::Relaxed);
-        let new_keys = new_keys_added.load(Ordering::Relaxed);
+        for hash in other.presence.iter() {
+            self.presence.insert(hash);
+        }
 
         println!("Added {} k-mer counts to the table", total_added);
         println!("Added {} new keys to the table", new_keys);
@@ -839,7 +1940,7 @@ impl KmerCountTable {
 #[pyclass]
 /// Iterator implementation for KmerCountTable
 pub struct KmerCountTableIterator {
-    inner: IntoIter<u64, u64>, // Now we own the iterator
+    inner: std::vec::IntoIter<(u64, u64)>, // Now we own the iterator
 }
 
 #[pymethods]
@@ -851,42 +1952,48 @@ impl KmerCountTableIterator {
 
 pub struct KmersAndHashesIter {
     seq: String,          // The sequence to iterate over
-    seq_rc: String,       // reverse complement sequence
     ksize: usize,         // K-mer size
     pos: usize,           // Current position in the sequence
     end: usize,           // The end position for k-mer extraction
     hasher: SeqToHashes,  // Iterator for generating hashes
     skip_bad_kmers: bool, // Flag to skip bad k-mers
+    encoding: Encoding,   // Alphabet the k-mers are interpreted in
 }
 
 impl KmersAndHashesIter {
-    pub fn new(seq: &str, ksize: usize, skip_bad_kmers: bool) -> Self {
+    pub fn new(
+        seq: &str,
+        ksize: usize,
+        skip_bad_kmers: bool,
+        encoding: Encoding,
+    ) -> PyResult<Self> {
         let seq = seq.to_ascii_uppercase(); // Ensure uppercase for uniformity
+        if seq.len() < ksize {
+            return Err(PyValueError::new_err(
+                "sequence is too short to contain a k-mer, or contains no valid k-mers",
+            ));
+        }
         let seqb = seq.as_bytes().to_vec(); // Convert to bytes for hashing
-        let seqb_rc = revcomp(&seqb);
-        let seq_rc = std::str::from_utf8(&seqb_rc)
-            .expect("invalid utf-8 sequence for rev comp")
-            .to_string();
 
         let end = seq.len() - ksize + 1; // Calculate the endpoint for k-mer extraction
         let hasher = SeqToHashes::new(
             &seqb,
             ksize,
-            true,  // Set force to true, bad kmers will emit hash=0 instead of killing process
-            false, // Other flags, e.g., reverse complement
-            HashFunctions::Murmur64Dna,
+            true, // Set force to true, bad kmers will emit hash=0 instead of killing process
+            !encoding.is_nucleotide(), // Whether the input is a protein-family alphabet
+            encoding.hash_function(),
             42, // Seed for hashing
         );
 
-        Self {
+        Ok(Self {
             seq,
-            seq_rc,
             ksize,
             pos: 0, // Start at the beginning of the sequence
             end,
             hasher,
             skip_bad_kmers,
-        }
+            encoding,
+        })
     }
 }
 
@@ -901,11 +2008,9 @@ impl Iterator for KmersAndHashesIter {
 
         let start = self.pos;
         let ksize = self.ksize;
-        let rpos = self.end - start - 1;
 
-        // Extract the current k-mer and its reverse complement
+        // Extract the current k-mer
         let substr = &self.seq[start..start + ksize];
-        let substr_rc = &self.seq_rc[rpos..rpos + ksize];
 
         // Get the next hash value from the hasher
         let hashval = self.hasher.next().expect("should not run out of hashes");
@@ -917,14 +2022,19 @@ impl Iterator for KmersAndHashesIter {
         if let Ok(hashval) = hashval {
             // Good kmer, all is well, store canonical k-mer and hashval;
             if hashval > 0 {
-                // Select the canonical k-mer (lexicographically smaller between forward and reverse complement)
-                let canonical_kmer = if substr < substr_rc {
-                    substr
+                let kmer_str = if self.encoding.is_nucleotide() {
+                    // Select the canonical k-mer via the 2-bit packed form:
+                    // its reverse complement is computed on the packed bits
+                    // and compared as a word array, rather than slicing and
+                    // comparing two `String`s per position.
+                    PackedKmer::encode(substr).canonical().decode()
                 } else {
-                    substr_rc
+                    // Amino-acid alphabets have no complementary strand,
+                    // so the k-mer is reported exactly as read.
+                    substr.to_string()
                 };
                 // If valid hash, return (canonical_kmer,hashval) tuple
-                Some(Ok((canonical_kmer.to_string(), hashval)))
+                Some(Ok((kmer_str, hashval)))
             } else {
                 // If the hash is 0, handle based on `skip_bad_kmers`
                 // Prepare msg identifying bad kmer
@@ -954,3 +2064,261 @@ fn oxli(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<KmerCountTable>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cms_does_not_track_an_exact_seen_set() {
+        let mut table = KmerCountTable::new_cms(4, 101, 3, false, None, "dna").unwrap();
+        table.count("ACGT".to_string()).unwrap();
+
+        // Enumeration-based operations must error instead of silently
+        // answering from an O(distinct k-mers) side structure, which would
+        // defeat the entire point of a bounded-memory counting backend.
+        assert!(table.hashes().is_err());
+        assert!(table.histo(true).is_err());
+        assert!(table.min().is_err());
+        assert!(table.max().is_err());
+        assert!(table.sum_counts().is_err());
+        assert!(table.dump(None, false, false).is_err());
+        assert!(table.mincut(1).is_err());
+
+        // The approximate count itself still works.
+        assert_eq!(table.get("ACGT".to_string()).unwrap(), 1);
+    }
+
+    #[test]
+    fn cms_estimate_is_never_below_the_true_count() {
+        // A tiny, heavily-collided sketch so the Count-Min Sketch's
+        // one-sided overcounting behavior actually gets exercised.
+        let mut cms_table = KmerCountTable::new_cms(4, 3, 2, false, None, "dna").unwrap();
+        let mut exact_table = KmerCountTable::new(4, false, None, "dna").unwrap();
+
+        for kmer in ["ACGT", "TTTT", "GGGG", "ACGT", "CCCC", "ACGT"] {
+            cms_table.count(kmer.to_string()).unwrap();
+            exact_table.count(kmer.to_string()).unwrap();
+        }
+
+        for kmer in ["ACGT", "TTTT", "GGGG", "CCCC"] {
+            let exact = exact_table.get(kmer.to_string()).unwrap();
+            let estimate = cms_table.get(kmer.to_string()).unwrap();
+            assert!(
+                estimate >= exact,
+                "Count-Min Sketch estimate {} was below the true count {} for {}",
+                estimate,
+                exact,
+                kmer
+            );
+        }
+    }
+
+    #[test]
+    fn cms_row_widths_are_pairwise_distinct_at_realistic_sizes() {
+        // At these widths, `next_prime(width + i)` collapses to the same
+        // prime for every row: the gap to the next prime routinely exceeds
+        // `depth`, so every row hashed the same way and the sketch's
+        // independent-rows error-reduction guarantee was silently lost.
+        for width in [1_000usize, 100_000, 10_000_000, 1_000_000_000] {
+            let depth = 4;
+            let table = CmsTable::new(width, depth);
+            let mut widths = table.widths.clone();
+            widths.sort_unstable();
+            widths.dedup();
+            assert_eq!(
+                widths.len(),
+                depth,
+                "widths were not pairwise distinct for width={}: {:?}",
+                width,
+                table.widths
+            );
+        }
+    }
+
+    #[test]
+    fn protein_dayhoff_and_hp_encodings_hash_amino_acid_kmers() {
+        // These alphabets have no complementary strand, so a hardcoded
+        // `is_protein=false` argument to `SeqToHashes::new` would previously
+        // have run DNA-style canonicalization (reverse-complement, base
+        // validation) against amino-acid input instead of the matching
+        // protein-family hash function.
+        for encoding in ["protein", "dayhoff", "hp"] {
+            let mut table = KmerCountTable::new(3, false, None, encoding).unwrap();
+            let count = table.count("MKV".to_string()).unwrap();
+            assert_eq!(count, 1);
+            assert_eq!(table.get("MKV".to_string()).unwrap(), 1);
+        }
+    }
+
+    #[test]
+    fn add_rejects_mismatched_encoding_and_scaled() {
+        let mut dna_table = KmerCountTable::new(3, false, None, "dna").unwrap();
+        let protein_table = KmerCountTable::new(3, false, None, "protein").unwrap();
+        assert!(dna_table.add(&protein_table).is_err());
+
+        let mut unscaled_table = KmerCountTable::new(3, false, None, "dna").unwrap();
+        let scaled_table = KmerCountTable::new(3, false, Some(1000), "dna").unwrap();
+        assert!(unscaled_table.add(&scaled_table).is_err());
+    }
+
+    #[test]
+    fn median_abundance_errors_instead_of_panicking_on_short_sequences() {
+        let table = KmerCountTable::new(4, false, None, "dna").unwrap();
+
+        // Shorter than ksize: used to underflow `seq.len() - ksize + 1` in
+        // KmersAndHashesIter::new and panic before this error was ever reached.
+        assert!(table.median_abundance("AC", true).is_err());
+
+        let mut table = table;
+        assert!(table.consume_with_diginorm("AC", 1.0, true).is_err());
+    }
+
+    /// The first `n` distinct DNA k-mers of length `ksize` (in base-4
+    /// order), used below as a close-to-known distinct count for the
+    /// HyperLogLog estimate to compare against. Reverse-complement pairs
+    /// among them share a canonical hash, so the number of distinct hashes
+    /// is slightly lower (1974 for the first 2000 8-mers).
+    fn distinct_dna_kmers(n: usize, ksize: usize) -> Vec<String> {
+        let bases = ['A', 'C', 'G', 'T'];
+        let mut kmers = Vec::new();
+        let mut i: u64 = 0;
+        while kmers.len() < n {
+            let kmer: String = (0..ksize)
+                .map(|pos| bases[((i >> (2 * pos)) & 0b11) as usize])
+                .collect();
+            i += 1;
+            kmers.push(kmer);
+        }
+        kmers
+    }
+
+    #[test]
+    fn hyperloglog_cardinality_is_close_to_the_true_distinct_count() {
+        let mut table = KmerCountTable::new(8, false, None, "dna").unwrap();
+        let kmers = distinct_dna_kmers(2000, 8);
+        for kmer in &kmers {
+            table.count(kmer.clone()).unwrap();
+        }
+
+        let estimate = table.cardinality() as f64;
+        let true_count = kmers.len() as f64;
+        let relative_error = (estimate - true_count).abs() / true_count;
+        assert!(
+            relative_error < 0.05,
+            "cardinality estimate {} too far from true count {} (relative error {})",
+            estimate,
+            true_count,
+            relative_error
+        );
+    }
+
+    #[test]
+    fn scaled_table_containment_and_jaccard_are_self_consistent() {
+        let mut table = KmerCountTable::new(8, false, Some(10), "dna").unwrap();
+        for kmer in distinct_dna_kmers(500, 8) {
+            table.count(kmer).unwrap();
+        }
+
+        assert_eq!(table.jaccard(&table).unwrap(), 1.0);
+        assert_eq!(table.containment(&table).unwrap(), 1.0);
+
+        let empty = KmerCountTable::new(8, false, Some(10), "dna").unwrap();
+        assert_eq!(table.containment(&empty).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn cardinality_reflects_the_full_stream_even_when_scaled_drops_most_hashes() {
+        let kmers = distinct_dna_kmers(2000, 8);
+
+        let mut unscaled = KmerCountTable::new(8, false, None, "dna").unwrap();
+        // A small scaled factor so most hashes are discarded rather than
+        // retained -- cardinality() used to silently report just the
+        // retained fraction's size in that case.
+        let mut scaled = KmerCountTable::new(8, false, Some(100), "dna").unwrap();
+        for kmer in &kmers {
+            unscaled.count(kmer.clone()).unwrap();
+            scaled.count(kmer.clone()).unwrap();
+        }
+
+        let unscaled_estimate = unscaled.cardinality() as f64;
+        let scaled_estimate = scaled.cardinality() as f64;
+        let relative_error = (scaled_estimate - unscaled_estimate).abs() / unscaled_estimate;
+        assert!(
+            relative_error < 0.1,
+            "scaled table's cardinality {} diverged from the unscaled table's {} \
+             (relative error {}) -- HyperLogLog should see every hash regardless of scaled",
+            scaled_estimate,
+            unscaled_estimate,
+            relative_error
+        );
+    }
+
+    #[test]
+    fn bray_curtis_and_angular_distance_are_zero_for_identical_tables() {
+        let mut table = KmerCountTable::new(4, false, None, "dna").unwrap();
+        for kmer in ["ACGT", "TTTT", "GGGG", "ACGT"] {
+            table.count(kmer.to_string()).unwrap();
+        }
+
+        assert!(table.bray_curtis(&table).unwrap().abs() < 1e-9);
+        assert!(table.angular_distance(&table).unwrap().abs() < 1e-9);
+    }
+
+    #[test]
+    fn set_ops_match_roaring_bitmap_and_or_sub_xor() {
+        let mut a = KmerCountTable::new(4, false, None, "dna").unwrap();
+        for kmer in ["ACGT", "TTTT", "GGGG"] {
+            a.count(kmer.to_string()).unwrap();
+        }
+        let mut b = KmerCountTable::new(4, false, None, "dna").unwrap();
+        for kmer in ["TTTT", "GGGG", "CCCC"] {
+            b.count(kmer.to_string()).unwrap();
+        }
+
+        let hash = |kmer: &str| a.hash_kmer(kmer.to_string()).unwrap();
+        let (acgt, tttt, gggg, cccc) = (hash("ACGT"), hash("TTTT"), hash("GGGG"), hash("CCCC"));
+
+        assert_eq!(
+            a.union(&b).unwrap(),
+            HashSet::from([acgt, tttt, gggg, cccc])
+        );
+        assert_eq!(a.intersection(&b).unwrap(), HashSet::from([tttt, gggg]));
+        assert_eq!(a.difference(&b).unwrap(), HashSet::from([acgt]));
+        assert_eq!(
+            a.symmetric_difference(&b).unwrap(),
+            HashSet::from([acgt, cccc])
+        );
+    }
+
+    #[test]
+    fn binary_save_and_load_round_trip_via_crc32_checked_file() {
+        let mut table = KmerCountTable::new(4, false, None, "dna").unwrap();
+        for kmer in ["ACGT", "TTTT", "GGGG"] {
+            table.count(kmer.to_string()).unwrap();
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "oxli_binary_round_trip_test_{}.bin",
+            std::process::id()
+        ));
+        let path_str = path.to_str().unwrap();
+
+        table.save_binary(path_str).unwrap();
+        let loaded = KmerCountTable::load_binary(path_str).unwrap();
+
+        assert_eq!(loaded.get("ACGT".to_string()).unwrap(), 2);
+        assert_eq!(loaded.get("TTTT".to_string()).unwrap(), 1);
+        assert_eq!(loaded.get("GGGG".to_string()).unwrap(), 1);
+
+        // Corrupting a payload byte must be caught by the trailing CRC32
+        // checksum rather than silently loading bad counts.
+        let mut bytes = std::fs::read(path_str).unwrap();
+        let corrupt_at = bytes.len() - 5;
+        bytes[corrupt_at] ^= 0xff;
+        std::fs::write(path_str, &bytes).unwrap();
+        assert!(KmerCountTable::load_binary(path_str).is_err());
+
+        std::fs::remove_file(path_str).ok();
+    }
+}